@@ -1,4 +1,7 @@
+use rayon::prelude::*;
 use walkdir::{DirEntry, WalkDir};
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
 #[cfg(test)]
 mod tests;
 
@@ -8,6 +11,128 @@ pub enum BlockSize {
     Mb(usize),
 }
 
+/// Scope a scan the way a command-line disk tool does: bound the walk depth,
+/// drop files below a size, skip entries matching glob patterns, and choose
+/// whether symlinks are followed into their targets
+
+#[derive(Default, Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PullConfig {
+    max_depth: Option<usize>,
+    min_size: Option<u64>,
+    exclude: Vec<String>,
+    follow_links: bool,
+    threads: usize,
+}
+
+impl PullConfig {
+    /// Create a config that walks the whole tree unconditionally
+
+    pub fn new() -> PullConfig {
+        PullConfig::default()
+    }
+
+    /// Limit the walk to at most depth levels below the root
+
+    pub fn max_depth(mut self, depth: usize) -> PullConfig {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Ignore files whose length is below size bytes
+
+    pub fn min_size(mut self, size: u64) -> PullConfig {
+        self.min_size = Some(size);
+        self
+    }
+
+    /// Skip entries whose file name matches the glob pattern
+
+    pub fn exclude(mut self, pattern: &str) -> PullConfig {
+        self.exclude.push(String::from(pattern));
+        self
+    }
+
+    /// Follow symbolic links so their targets are measured instead of the links
+
+    pub fn follow_links(mut self, follow: bool) -> PullConfig {
+        self.follow_links = follow;
+        self
+    }
+
+    /// Size the worker pool used to stat entries; 0 (the default) picks one
+    /// worker per logical CPU
+
+    pub fn threads(mut self, n: usize) -> PullConfig {
+        self.threads = n;
+        self
+    }
+}
+
+/// Select whether a size statistic reports the apparent file length or the
+/// space the file actually occupies on disk
+pub enum SizeKind {
+    Apparent,
+    Allocated,
+}
+
+/// Number of bytes in the block unit reported by the Unix `blocks()` count
+
+const BLOCK_BYTES: u64 = 512;
+
+/// Allocated block count of an entry, or None when the platform cannot report
+/// it
+
+#[cfg(unix)]
+fn blocks_of(m: &std::fs::Metadata) -> Option<u64> {
+    Some(m.blocks())
+}
+
+#[cfg(not(unix))]
+fn blocks_of(_m: &std::fs::Metadata) -> Option<u64> {
+    None
+}
+
+/// An owned, self-contained record of one scanned entry. Holding the measured
+/// values rather than a `walkdir::DirEntry` lets the same accessors run over a
+/// freshly walked tree or over data reloaded from a snapshot.
+
+#[derive(Debug, Clone)]
+struct Entry {
+    path: std::path::PathBuf,
+    depth: usize,
+    kind: EntryKind,
+    len: u64,
+    blocks: Option<u64>,
+    mtime: i64,
+}
+
+impl Entry {
+    /// File name component of the entry's path
+
+    fn file_name(&self) -> &str {
+        self.path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("")
+    }
+
+    /// Whether the entry's file name marks it as hidden
+
+    fn is_hidden(&self) -> bool {
+        self.file_name().starts_with('.')
+    }
+
+    /// Size of the entry measured according to kind; the on-disk footprint falls
+    /// back to the apparent length when the block count is unknown
+
+    fn size(&self, kind: &SizeKind) -> u64 {
+        match kind {
+            SizeKind::Apparent => self.len,
+            SizeKind::Allocated => self.blocks.map(|b| b * BLOCK_BYTES).unwrap_or(self.len),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Error {
     error: Option<std::io::Error>,
@@ -36,13 +161,207 @@ impl Error {
     }
 }
 
+/// Aggregated view of a single directory: its own direct file size, the
+/// recursively summed size of everything beneath it, the file and subdirectory
+/// counts beneath it, and its immediate children
+
+#[derive(Debug)]
+pub struct DirNode {
+    pub path: std::path::PathBuf,
+    pub direct_size: u64,
+    pub recursive_size: u64,
+    pub file_count: usize,
+    pub subdir_count: usize,
+    pub children: Vec<DirNode>,
+}
+
+/// Mutable accumulator used while rolling file sizes up the directory chain
+
+#[derive(Default)]
+struct NodeAgg {
+    direct_size: u64,
+    recursive_size: u64,
+    file_count: usize,
+    subdir_count: usize,
+    children: Vec<std::path::PathBuf>,
+}
+
+/// Format version stamped into every snapshot so a future layout change can be
+/// detected and rejected on load
+
+const SNAPSHOT_VERSION: u32 = 2;
+
+/// Kind of a snapshotted entry, mirroring the directory/file/symlink split
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq)]
+pub enum EntryKind {
+    Directory,
+    File,
+    Symlink,
+}
+
+/// A single entry preserved in a snapshot: its path, apparent size, depth,
+/// kind, and last-modified time in seconds since the Unix epoch
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct SnapshotEntry {
+    pub path: std::path::PathBuf,
+    pub size: u64,
+    pub depth: usize,
+    pub kind: EntryKind,
+    pub mtime: i64,
+}
+
+/// A serializable, reloadable record of a completed scan that can be revalidated
+/// against the filesystem instead of re-walking the whole tree each run
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct Snapshot {
+    version: u32,
+    root: std::path::PathBuf,
+    config: PullConfig,
+    entries: Vec<SnapshotEntry>,
+}
+
+/// Classify a file type into the directory/file/symlink split
+
+fn kind_of(ft: std::fs::FileType) -> EntryKind {
+    if ft.is_dir() {
+        EntryKind::Directory
+    } else if ft.is_symlink() {
+        EntryKind::Symlink
+    } else {
+        EntryKind::File
+    }
+}
+
+/// Last-modified time of metadata in whole seconds since the Unix epoch
+
+fn mtime_secs(m: &std::fs::Metadata) -> i64 {
+    m.modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+impl Snapshot {
+    /// Write the snapshot to path as compact JSON
+
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        let data = serde_json::to_vec(self)?;
+        std::fs::write(path, data)
+    }
+
+    /// Load a snapshot from path, rejecting any file whose version header does
+    /// not match the current format
+
+    pub fn load(path: &str) -> std::io::Result<Snapshot> {
+        let data = std::fs::read(path)?;
+        let snap: Snapshot = serde_json::from_slice(&data)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        if snap.version != SNAPSHOT_VERSION {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "unsupported snapshot version {} (expected {})",
+                    snap.version, SNAPSHOT_VERSION
+                ),
+            ));
+        }
+        Ok(snap)
+    }
+
+    /// Re-stat the directory mtime chain and rescan only the subtrees whose
+    /// directory mtime has changed since the snapshot, leaving the rest cached
+
+    pub fn refresh(&mut self) -> std::io::Result<()> {
+        let mut stale: Vec<std::path::PathBuf> = Vec::new();
+        for entry in &self.entries {
+            if entry.kind != EntryKind::Directory {
+                continue;
+            }
+            match std::fs::metadata(&entry.path) {
+                Ok(m) if mtime_secs(&m) != entry.mtime => stale.push(entry.path.clone()),
+                Err(_) => stale.push(entry.path.clone()),
+                _ => {}
+            }
+        }
+        // Keep only the top-most stale directory of each chain so overlapping
+        // subtrees are rescanned once.
+        stale.sort();
+        let roots: Vec<std::path::PathBuf> = stale
+            .iter()
+            .filter(|p| !stale.iter().any(|o| o.as_path() != p.as_path() && p.starts_with(o)))
+            .cloned()
+            .collect();
+        let patterns: Vec<glob::Pattern> = self
+            .config
+            .exclude
+            .iter()
+            .filter_map(|p| glob::Pattern::new(p).ok())
+            .collect();
+        for dir in roots {
+            let base = self
+                .entries
+                .iter()
+                .find(|e| e.path == dir)
+                .map(|e| e.depth)
+                .unwrap_or(0);
+            self.entries.retain(|e| !e.path.starts_with(&dir));
+            // Re-apply the original scan config, offsetting its depth budget by
+            // how deep this subtree already sits below the scan root.
+            let mut walker = WalkDir::new(&dir).follow_links(self.config.follow_links);
+            if let Some(depth) = self.config.max_depth {
+                walker = walker.max_depth(depth.saturating_sub(base));
+            }
+            for de in walker
+                .into_iter()
+                .filter_entry(|d| {
+                    let name = d.file_name().to_str().unwrap();
+                    !patterns.iter().any(|p| p.matches(name))
+                })
+                .filter_map(|de| de.ok())
+            {
+                let meta = match de.metadata() {
+                    Ok(m) => m,
+                    Err(_) => continue,
+                };
+                if de.file_type().is_file() {
+                    if let Some(min) = self.config.min_size {
+                        if meta.len() < min {
+                            continue;
+                        }
+                    }
+                }
+                let kind = kind_of(de.file_type());
+                self.entries.push(SnapshotEntry {
+                    path: de.path().to_path_buf(),
+                    size: meta.len(),
+                    depth: base + de.depth(),
+                    kind,
+                    mtime: mtime_secs(&meta),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Entries preserved in this snapshot
+
+    pub fn entries(&self) -> &Vec<SnapshotEntry> {
+        &self.entries
+    }
+}
+
 #[derive(Debug)]
 pub struct DirInfo {
-    all: Option<Vec<DirEntry>>,
+    all: Option<Vec<Entry>>,
     errors: Option<Vec<Error>>,
-    directories: Option<Vec<DirEntry>>,
-    files: Option<Vec<DirEntry>>,
-    symlinks: Option<Vec<DirEntry>>,
+    directories: Option<Vec<Entry>>,
+    files: Option<Vec<Entry>>,
+    symlinks: Option<Vec<Entry>>,
+    config: PullConfig,
 }
 
 impl DirInfo {
@@ -55,35 +374,177 @@ impl DirInfo {
             directories: None,
             files: None,
             symlinks: None,
+            config: PullConfig::new(),
         }
     }
 
+    /// Reconstruct a DirInfo from a reloaded snapshot so every analysis accessor
+    /// runs against cached data without re-walking the tree. On-disk (allocated)
+    /// sizes fall back to apparent length, which a snapshot does not record.
+
+    pub fn from_snapshot(snap: &Snapshot) -> DirInfo {
+        let all: Vec<Entry> = snap
+            .entries
+            .iter()
+            .map(|e| Entry {
+                path: e.path.clone(),
+                depth: e.depth,
+                kind: e.kind,
+                len: e.size,
+                blocks: None,
+                mtime: e.mtime,
+            })
+            .collect();
+        DirInfo {
+            all: Some(all),
+            errors: Some(Vec::new()),
+            directories: None,
+            files: None,
+            symlinks: None,
+            config: snap.config.clone(),
+        }
+        .all_directories()
+        .all_files()
+        .all_symlinks()
+    }
+
+    /// Paths that could not be measured during the scan, with the io error and
+    /// depth at which each was encountered
+
+    pub fn errors(&self) -> &[Error] {
+        match self.errors {
+            Some(ref errors) => errors,
+            _ => &[],
+        }
+    }
+
+    /// Total number of entries that could not be measured during the scan
+
+    pub fn num_errors(&self) -> usize {
+        self.errors().len()
+    }
+
+    /// Distribution of unmeasurable entries by depth level in the hierarchy
+
+    pub fn errors_by_depth(&self) -> Vec<u32> {
+        let errors = self.errors();
+        let deepest = errors.iter().fold(0, |max, e| e.depth.max(max));
+        let mut depth_distri = vec![0u32; deepest];
+        errors.iter().for_each(|e| {
+            if e.depth > 0 {
+                depth_distri[e.depth - 1] += 1
+            }
+        });
+        depth_distri
+    }
+
     /// Populate DirInfo fields with directory information pulled with root_dir arg
     /// directory specifying the root directory to pull from
 
     pub fn pull(self, root_dir: &str) -> DirInfo {
-        self.all(root_dir)
+        self.pull_with(root_dir, PullConfig::new())
+    }
+
+    /// Populate DirInfo fields with directory information pulled with root_dir arg
+    /// scoped by the supplied config
+
+    pub fn pull_with(self, root_dir: &str, config: PullConfig) -> DirInfo {
+        self.all(root_dir, config)
             .all_directories()
             .all_files()
             .all_symlinks()
     }
 
-    fn all(mut self, root: &str) -> DirInfo {
+    fn all(mut self, root: &str, config: PullConfig) -> DirInfo {
+        let patterns: Vec<glob::Pattern> = config
+            .exclude
+            .iter()
+            .filter_map(|p| glob::Pattern::new(p).ok())
+            .collect();
+        let mut walker = WalkDir::new(root).follow_links(config.follow_links);
+        if let Some(depth) = config.max_depth {
+            walker = walker.max_depth(depth);
+        }
         let mut direntries: Vec<DirEntry> = Vec::new();
         let mut errors: Vec<Error> = Vec::new();
-        WalkDir::new(root).into_iter().for_each(|de| match de {
-            Ok(d) => direntries.push(d),
-            Err(e) => errors.push(Error::from(e)),
-        });
-        self.all = Some(direntries);
+        walker
+            .into_iter()
+            .filter_entry(|d| {
+                let name = d.file_name().to_str().unwrap();
+                !patterns.iter().any(|p| p.matches(name))
+            })
+            .for_each(|de| match de {
+                Ok(d) => {
+                    if d.file_type().is_file() {
+                        if let Some(min) = config.min_size {
+                            if let Ok(m) = d.metadata() {
+                                if m.len() < min {
+                                    return;
+                                }
+                            }
+                        }
+                    }
+                    direntries.push(d);
+                }
+                Err(e) => errors.push(Error::from(e)),
+            });
+        let threads = if config.threads == 0 {
+            num_cpus::get()
+        } else {
+            config.threads
+        };
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+        let stats: Vec<Result<Entry, (std::path::PathBuf, usize, std::io::Error)>> =
+            pool.install(|| {
+                direntries
+                    .par_iter()
+                    .map(|d| {
+                        let path = d.path().to_path_buf();
+                        let depth = d.depth();
+                        match d.metadata() {
+                            Ok(m) => Ok(Entry {
+                                path,
+                                depth,
+                                kind: kind_of(d.file_type()),
+                                len: m.len(),
+                                blocks: blocks_of(&m),
+                                mtime: mtime_secs(&m),
+                            }),
+                            Err(e) => {
+                                let kind = e
+                                    .io_error()
+                                    .map(|er| er.kind())
+                                    .unwrap_or(std::io::ErrorKind::Other);
+                                Err((path, depth, std::io::Error::new(kind, e)))
+                            }
+                        }
+                    })
+                    .collect()
+            });
+        let mut all: Vec<Entry> = Vec::with_capacity(stats.len());
+        for stat in stats {
+            match stat {
+                Ok(entry) => all.push(entry),
+                Err((path, depth, io)) => errors.push(Error::new(
+                    Some(path.to_string_lossy().into_owned()),
+                    depth,
+                    Some(io),
+                )),
+            }
+        }
+        self.all = Some(all);
         self.errors = Some(errors);
+        self.config = config;
         self
     }
 
     /// For all files found in the directory hierarchy, create a histogram of file
     /// sizes with the bin size of histogram specified by blocksize arg
 
-    pub fn get_file_size_distribution(&self, blocksize: BlockSize) -> Vec<usize> {
+    pub fn get_file_size_distribution(&self, blocksize: BlockSize, kind: SizeKind) -> Vec<usize> {
         let blk: usize = match blocksize {
             BlockSize::Kb100 => 100_000usize,
             BlockSize::Kb500 => 500_000usize,
@@ -91,8 +552,9 @@ impl DirInfo {
         };
         let biggest = if let Some(ref files) = self.files {
             files.into_iter().fold(0, |max, d| {
-                if d.metadata().unwrap().len() > max {
-                    d.metadata().unwrap().len()
+                let size = d.size(&kind);
+                if size > max {
+                    size
                 } else {
                     max
                 }
@@ -103,7 +565,7 @@ impl DirInfo {
         let mut distribution: Vec<usize> = vec![0; (biggest as usize / blk) + 1];
         if let Some(ref files) = self.files {
             files.into_iter().for_each(|f| {
-                distribution[f.metadata().unwrap().len() as usize / blk as usize] += 1
+                distribution[f.size(&kind) as usize / blk as usize] += 1
             });
         }
         distribution
@@ -112,11 +574,11 @@ impl DirInfo {
     /// Calculate the total file size in bytes for all the files found in directory
     /// hierarchy
 
-    pub fn get_files_size(&self) -> usize {
+    pub fn get_files_size(&self, kind: SizeKind) -> usize {
         match self.files {
             Some(ref files) => files
                 .iter()
-                .fold(0, |acc, s| acc + s.metadata().unwrap().len() as usize),
+                .fold(0, |acc, s| acc + s.size(&kind) as usize),
             _ => 0,
         }
     }
@@ -124,12 +586,12 @@ impl DirInfo {
     /// Calculate the total file size in bytes for all files with file extension
     /// of ext arg found in directory hierarchy
 
-    pub fn get_files_size_by_file_ext(&self, ext: &str) -> usize {
+    pub fn get_files_size_by_file_ext(&self, ext: &str, kind: SizeKind) -> usize {
         match self.files {
             Some(ref files) => files
                 .iter()
-                .filter(|f| f.file_name().to_str().unwrap().ends_with(ext))
-                .fold(0, |acc, f| acc + f.metadata().unwrap().len() as usize),
+                .filter(|f| f.file_name().ends_with(ext))
+                .fold(0, |acc, f| acc + f.size(&kind) as usize),
             _ => 0,
         }
     }
@@ -141,7 +603,7 @@ impl DirInfo {
         match self.files {
             Some(ref files) => files
                 .iter()
-                .filter(|f| f.file_name().to_str().unwrap().ends_with(ext))
+                .filter(|f| f.file_name().ends_with(ext))
                 .fold(0, |acc, _f| acc + 1),
             _ => 0,
         }
@@ -150,12 +612,12 @@ impl DirInfo {
     /// Calculate the total file size in bytes for all hidden files found in directory
     /// hierarchy
 
-    pub fn get_hidden_files_size(&self) -> usize {
+    pub fn get_hidden_files_size(&self, kind: SizeKind) -> usize {
         match self.files {
             Some(ref files) => files
                 .iter()
-                .filter(|f| f.file_name().to_str().unwrap().starts_with("."))
-                .fold(0, |acc, f| acc + f.metadata().unwrap().len() as usize),
+                .filter(|f| f.is_hidden())
+                .fold(0, |acc, f| acc + f.size(&kind) as usize),
             _ => 0,
         }
     }
@@ -175,7 +637,7 @@ impl DirInfo {
         match self.files {
             Some(ref files) => files
                 .iter()
-                .filter(|f| f.file_name().to_str().unwrap().starts_with("."))
+                .filter(|f| f.is_hidden())
                 .fold(0, |acc, _f| acc + 1),
             _ => 0,
         }
@@ -197,7 +659,7 @@ impl DirInfo {
         match self.directories {
             Some(ref directories) => directories
                 .iter()
-                .filter(|f| f.file_name().to_str().unwrap().starts_with("."))
+                .filter(|f| f.is_hidden())
                 .fold(0, |acc, _f| acc + 1),
             _ => 0,
         }
@@ -212,10 +674,10 @@ impl DirInfo {
         }
     }
 
-    fn deepest_depth(files: &Vec<DirEntry>) -> usize {
+    fn deepest_depth(files: &Vec<Entry>) -> usize {
         files
             .iter()
-            .fold(0, |max, d| if d.depth() > max { d.depth() } else { max })
+            .fold(0, |max, d| if d.depth > max { d.depth } else { max })
     }
 
     /// Identify maximum depth of directory hierarchy
@@ -228,12 +690,12 @@ impl DirInfo {
         }
     }
 
-    fn entry_depth_distri(entries: &Vec<DirEntry>) -> Vec<u32> {
+    fn entry_depth_distri(entries: &Vec<Entry>) -> Vec<u32> {
         let deepest = Self::deepest_depth(entries);
         let mut depth_distri = vec![0u32; deepest];
         entries.iter().for_each(|f| {
-            if f.depth() > 0 {
-                depth_distri[f.depth() - 1] += 1
+            if f.depth > 0 {
+                depth_distri[f.depth - 1] += 1
             }
         });
         depth_distri
@@ -273,11 +735,11 @@ impl DirInfo {
 
     /// Calculate distribution of file size by depth level in directory hierarchy
 
-    pub fn get_files_size_by_depth(&self) -> Vec<usize> {
+    pub fn get_files_size_by_depth(&self, kind: SizeKind) -> Vec<usize> {
         if let Some(ref files) = self.files {
             let deepest = Self::deepest_depth(files);
             files.iter().fold(vec![0usize; deepest], |mut acc, f| {
-                acc[f.depth() - 1] += f.metadata().unwrap().len() as usize;
+                acc[f.depth - 1] += f.size(&kind) as usize;
                 acc
             })
         } else {
@@ -287,12 +749,12 @@ impl DirInfo {
 
     /// Calculate distribution of hidden file size by depth level in directory hierarchy
 
-    pub fn get_hidden_files_size_by_depth(&self) -> Vec<usize> {
+    pub fn get_hidden_files_size_by_depth(&self, kind: SizeKind) -> Vec<usize> {
         if let Some(ref files) = self.files {
             let deepest = Self::deepest_depth(files);
             files.iter().fold(vec![0usize; deepest], |mut acc, f| {
-                if f.file_name().to_str().unwrap().starts_with(".") {
-                    acc[f.depth() - 1] += f.metadata().unwrap().len() as usize;
+                if f.is_hidden() {
+                    acc[f.depth - 1] += f.size(&kind) as usize;
                 }
                 acc
             })
@@ -310,10 +772,10 @@ impl DirInfo {
             let mut depth_distri = vec![0u32; deepest];
             files
                 .iter()
-                .filter(|f| f.file_name().to_str().unwrap().starts_with("."))
+                .filter(|f| f.is_hidden())
                 .for_each(|f| {
-                    if f.depth() > 0 {
-                        depth_distri[f.depth() - 1] += 1
+                    if f.depth > 0 {
+                        depth_distri[f.depth - 1] += 1
                     }
                 });
             depth_distri
@@ -322,11 +784,192 @@ impl DirInfo {
         }
     }
 
+    /// Build the aggregation map keyed by directory path by rolling every file's
+    /// size up through its enclosing directories
+
+    fn rollups(&self) -> std::collections::HashMap<std::path::PathBuf, NodeAgg> {
+        let mut aggs: std::collections::HashMap<std::path::PathBuf, NodeAgg> =
+            std::collections::HashMap::new();
+        if let Some(ref directories) = self.directories {
+            for d in directories {
+                aggs.entry(d.path.clone()).or_default();
+            }
+            for d in directories {
+                if let Some(parent) = d.path.parent() {
+                    if let Some(agg) = aggs.get_mut(parent) {
+                        agg.children.push(d.path.clone());
+                    }
+                }
+                for anc in d.path.ancestors().skip(1) {
+                    if let Some(agg) = aggs.get_mut(anc) {
+                        agg.subdir_count += 1;
+                    }
+                }
+            }
+        }
+        if let Some(ref files) = self.files {
+            for f in files {
+                let size = f.len;
+                if let Some(parent) = f.path.parent() {
+                    if let Some(agg) = aggs.get_mut(parent) {
+                        agg.direct_size += size;
+                    }
+                }
+                for anc in f.path.ancestors().skip(1) {
+                    if let Some(agg) = aggs.get_mut(anc) {
+                        agg.recursive_size += size;
+                        agg.file_count += 1;
+                    }
+                }
+            }
+        }
+        aggs
+    }
+
+    /// Assemble a nested DirNode for path from the aggregation map
+
+    fn build_node(
+        path: &std::path::Path,
+        aggs: &std::collections::HashMap<std::path::PathBuf, NodeAgg>,
+    ) -> DirNode {
+        let agg = aggs.get(path).unwrap();
+        let children = agg
+            .children
+            .iter()
+            .map(|c| Self::build_node(c, aggs))
+            .collect();
+        DirNode {
+            path: path.to_path_buf(),
+            direct_size: agg.direct_size,
+            recursive_size: agg.recursive_size,
+            file_count: agg.file_count,
+            subdir_count: agg.subdir_count,
+            children,
+        }
+    }
+
+    /// Build a per-directory size tree rooted at the scanned directory, where
+    /// each node carries its direct and recursive size plus the counts beneath it
+
+    pub fn get_directory_tree(&self) -> Option<DirNode> {
+        let root = self
+            .directories
+            .as_ref()?
+            .iter()
+            .find(|d| d.depth == 0)?
+            .path
+            .clone();
+        let aggs = self.rollups();
+        Some(Self::build_node(&root, &aggs))
+    }
+
+    /// Return the n heaviest directories by recursive size, descending
+
+    pub fn get_largest_directories(&self, n: usize) -> Vec<(std::path::PathBuf, u64)> {
+        let aggs = self.rollups();
+        let mut dirs: Vec<(std::path::PathBuf, u64)> = aggs
+            .into_iter()
+            .map(|(p, a)| (p, a.recursive_size))
+            .collect();
+        dirs.sort_by(|a, b| b.1.cmp(&a.1));
+        dirs.truncate(n);
+        dirs
+    }
+
+    /// Coarse file category inferred from a file name's extension, falling back
+    /// to the lowercased extension itself and "none" when there is none
+
+    fn category(name: &str) -> String {
+        let ext = match name.rsplit_once('.') {
+            Some((base, ext)) if !ext.is_empty() && !base.is_empty() => ext.to_lowercase(),
+            _ => return String::from("none"),
+        };
+        let group = match ext.as_str() {
+            "jpg" | "jpeg" | "png" | "gif" | "bmp" | "svg" | "webp" => "image",
+            "mp4" | "mkv" | "mov" | "avi" | "webm" => "video",
+            "mp3" | "flac" | "wav" | "ogg" | "m4a" => "audio",
+            "zip" | "gz" | "tar" | "bz2" | "xz" | "7z" | "rar" => "archive",
+            "rs" | "c" | "h" | "cpp" | "py" | "js" | "ts" | "go" | "java" => "source",
+            "txt" | "md" | "pdf" | "doc" | "docx" | "odt" => "document",
+            _ => return ext,
+        };
+        String::from(group)
+    }
+
+    /// Group files by coarse type, returning the file count and total apparent
+    /// size for each category
+
+    pub fn get_size_by_type(&self) -> indexmap::IndexMap<String, (usize, u64)> {
+        let mut groups: indexmap::IndexMap<String, (usize, u64)> = indexmap::IndexMap::new();
+        if let Some(ref files) = self.files {
+            files.iter().for_each(|f| {
+                let cat = Self::category(f.file_name());
+                let size = f.len;
+                let entry = groups.entry(cat).or_insert((0, 0));
+                entry.0 += 1;
+                entry.1 += size;
+            });
+        }
+        groups
+    }
+
+    /// Return the n largest files as (path, size) sorted descending, using a
+    /// bounded heap so memory stays O(n)
+
+    pub fn get_largest_files(&self, n: usize) -> Vec<(std::path::PathBuf, u64)> {
+        let mut heap: std::collections::BinaryHeap<std::cmp::Reverse<(u64, std::path::PathBuf)>> =
+            std::collections::BinaryHeap::new();
+        if n > 0 {
+            if let Some(ref files) = self.files {
+                files.iter().for_each(|f| {
+                    let size = f.len;
+                    heap.push(std::cmp::Reverse((size, f.path.clone())));
+                    if heap.len() > n {
+                        heap.pop();
+                    }
+                });
+            }
+        }
+        let mut largest: Vec<(std::path::PathBuf, u64)> = heap
+            .into_iter()
+            .map(|std::cmp::Reverse((size, path))| (path, size))
+            .collect();
+        largest.sort_by(|a, b| b.1.cmp(&a.1));
+        largest
+    }
+
+    /// Capture a serializable snapshot of this completed scan for later reload
+
+    pub fn snapshot(&self) -> Snapshot {
+        let mut entries: Vec<SnapshotEntry> = Vec::new();
+        let mut root = std::path::PathBuf::new();
+        if let Some(ref all) = self.all {
+            for d in all {
+                if d.depth == 0 {
+                    root = d.path.clone();
+                }
+                entries.push(SnapshotEntry {
+                    path: d.path.clone(),
+                    size: d.len,
+                    depth: d.depth,
+                    kind: d.kind,
+                    mtime: d.mtime,
+                });
+            }
+        }
+        Snapshot {
+            version: SNAPSHOT_VERSION,
+            root,
+            config: self.config.clone(),
+            entries,
+        }
+    }
+
     fn all_directories(mut self) -> DirInfo {
-        let mut entries: Vec<DirEntry> = Vec::new();
+        let mut entries: Vec<Entry> = Vec::new();
         if let Some(ref all) = self.all {
             for entry in all {
-                if entry.file_type().is_dir() {
+                if entry.kind == EntryKind::Directory {
                     entries.push(entry.clone());
                 }
             }
@@ -336,10 +979,10 @@ impl DirInfo {
     }
 
     fn all_files(mut self) -> DirInfo {
-        let mut entries: Vec<DirEntry> = Vec::new();
+        let mut entries: Vec<Entry> = Vec::new();
         if let Some(ref all) = self.all {
             for entry in all {
-                if entry.file_type().is_file() {
+                if entry.kind == EntryKind::File {
                     entries.push(entry.clone());
                 }
             }
@@ -349,10 +992,10 @@ impl DirInfo {
     }
 
     fn all_symlinks(mut self) -> DirInfo {
-        let mut entries: Vec<DirEntry> = Vec::new();
+        let mut entries: Vec<Entry> = Vec::new();
         if let Some(ref all) = self.all {
             for entry in all {
-                if entry.file_type().is_symlink() {
+                if entry.kind == EntryKind::Symlink {
                     entries.push(entry.clone());
                 }
             }
@@ -1,8 +1,13 @@
-use super::{BlockSize, DirInfo};
+use super::{BlockSize, DirInfo, SizeKind};
 
 #[test]
 fn filesizedistribydepth() {
-    println!("{:#?}", DirInfo::new().pull(".").get_files_size_by_depth());
+    println!(
+        "{:#?}",
+        DirInfo::new()
+            .pull(".")
+            .get_files_size_by_depth(SizeKind::Apparent)
+    );
 }
 
 #[test]
@@ -31,7 +36,7 @@ fn distribution() {
         "{:#?}",
         DirInfo::new()
             .pull(".")
-            .get_file_size_distribution(BlockSize::Kb100)
+            .get_file_size_distribution(BlockSize::Kb100, SizeKind::Apparent)
     );
 }
 
@@ -41,6 +46,23 @@ fn splitfiles() {
     println!("{:#?} ", d);
 }
 
+#[test]
+fn snapshotroundtrip() {
+    let d = DirInfo::new().pull(".");
+    let reloaded = DirInfo::from_snapshot(&d.snapshot());
+    println!(
+        "{} {}",
+        d.get_files_size(SizeKind::Apparent),
+        reloaded.get_files_size(SizeKind::Apparent)
+    );
+}
+
+#[test]
+fn scanerrors() {
+    let d = DirInfo::new().pull("/etc");
+    println!("{} {:#?}", d.num_errors(), d.errors_by_depth());
+}
+
 #[test]
 fn byabsolutepath() {
     println!(
@@ -53,7 +75,12 @@ fn byabsolutepath() {
 
 #[test]
 fn hiddenfilesize() {
-    println!("{}", DirInfo::new().pull("../..").get_hidden_files_size());
+    println!(
+        "{}",
+        DirInfo::new()
+            .pull("../..")
+            .get_hidden_files_size(SizeKind::Apparent)
+    );
 }
 
 #[test]
@@ -67,7 +94,7 @@ fn filesizebyext() {
         "{}",
         DirInfo::new()
             .pull("/etc")
-            .get_files_size_by_file_ext(".conf")
+            .get_files_size_by_file_ext(".conf", SizeKind::Apparent)
     );
 }
 
@@ -78,7 +105,10 @@ fn dirinfonew() {
 
 #[test]
 fn filesize() {
-    println!("{}", DirInfo::new().pull("../..").get_files_size());
+    println!(
+        "{}",
+        DirInfo::new().pull("../..").get_files_size(SizeKind::Apparent)
+    );
 }
 
 #[test]